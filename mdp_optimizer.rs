@@ -4,7 +4,16 @@ use std::fs::File;
 use std::io::Write;
 use serde::{Serialize, Deserialize};
 use rand::Rng;
-use rand::distributions::{Distribution, Normal};
+use rayon::prelude::*;
+
+// Whether unmet demand is lost forever (LostSales, charged a one-time stockout cost) or
+// carried as a backlog that inventory can go negative to represent (Backorder, charged a
+// per-period cost on the outstanding backlog until it's filled).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum FulfillmentMode {
+    LostSales,
+    Backorder,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MDPConfig {
@@ -16,6 +25,11 @@ struct MDPConfig {
     demand_mean: f64,
     demand_std: f64,
     gamma: f64,
+    max_pipeline_qty: usize,
+    price_levels: Vec<f64>,
+    fulfillment_mode: FulfillmentMode,
+    max_backorder: usize,
+    backorder_cost: f64,
 }
 
 impl Default for MDPConfig {
@@ -29,29 +43,222 @@ impl Default for MDPConfig {
             demand_mean: 10.0,
             demand_std: 3.0,
             gamma: 0.95,
+            max_pipeline_qty: 5,
+            price_levels: vec![10.0, 12.5, 15.0, 17.5, 20.0],
+            fulfillment_mode: FulfillmentMode::LostSales,
+            max_backorder: 20,
+            backorder_cost: 8.0,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+// Maps a candidate selling price (and, for markdown-style adapters, the current on-hand
+// level) to the demand mean that price is expected to realize. The demand distribution's
+// shape (e.g. coefficient of variation) is held fixed; only its mean shifts with price.
+trait PriceAdapter: std::fmt::Debug + Send + Sync {
+    fn demand_mean(&self, price: f64, on_hand: usize, config: &MDPConfig) -> f64;
+
+    // The price actually charged per unit sold. Defaults to the nominal `price`; adapters that
+    // stimulate demand with a markdown (e.g. `LinearMarkdown`) must also bill revenue at that
+    // marked-down price, or the model gets the demand benefit of a price cut without ever
+    // paying for it.
+    fn effective_price(&self, price: f64, _on_hand: usize, _config: &MDPConfig) -> f64 {
+        price
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ConstantElasticity {
+    elasticity: f64,
+}
+
+impl PriceAdapter for ConstantElasticity {
+    fn demand_mean(&self, price: f64, _on_hand: usize, config: &MDPConfig) -> f64 {
+        config.demand_mean * (price / config.selling_price).powf(-self.elasticity)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LinearMarkdown {
+    elasticity: f64,
+    markdown_rate: f64,
+}
+
+impl PriceAdapter for LinearMarkdown {
+    fn demand_mean(&self, price: f64, on_hand: usize, config: &MDPConfig) -> f64 {
+        let effective_price = self.effective_price(price, on_hand, config);
+        config.demand_mean * (effective_price / config.selling_price).powf(-self.elasticity)
+    }
+
+    fn effective_price(&self, price: f64, on_hand: usize, config: &MDPConfig) -> f64 {
+        let on_hand_fraction = on_hand as f64 / config.max_inventory as f64;
+        (price * (1.0 - self.markdown_rate * on_hand_fraction)).max(0.01)
+    }
+}
+
+// Demand distributions over the non-negative integer support 0..=max_demand (or, for
+// `Empirical`, over the support implied by its fitted samples). Every variant's `pmf` sums to
+// 1 over its support, unlike plugging a continuous density straight in as a probability.
+#[derive(Debug, Clone)]
+enum DemandModel {
+    Normal,
+    Poisson,
+    NegativeBinomial,
+    Empirical { pmf: Vec<f64> },
+}
+
+impl DemandModel {
+    // Tallies historical demand samples into a frequency-based pmf.
+    fn fit_empirical(samples: &[usize]) -> DemandModel {
+        let max_demand = samples.iter().copied().max().unwrap_or(0);
+        let mut counts = vec![0usize; max_demand + 1];
+        for &sample in samples {
+            counts[sample] += 1;
+        }
+        let total = (samples.len().max(1)) as f64;
+        let pmf = counts.iter().map(|&count| count as f64 / total).collect();
+        DemandModel::Empirical { pmf }
+    }
+
+    fn normal_density(x: f64, mean: f64, std: f64) -> f64 {
+        let exponent = -0.5 * ((x - mean) / std).powi(2);
+        (1.0 / (std * (2.0 * PI).sqrt())) * exponent.exp()
+    }
+
+    fn normalize(mut raw: Vec<f64>) -> Vec<f64> {
+        let total: f64 = raw.iter().sum();
+        if total > 0.0 {
+            for p in raw.iter_mut() {
+                *p /= total;
+            }
+        }
+        raw
+    }
+
+    // Probability mass function over 0..=max_demand, renormalized so it sums to 1.
+    // `Empirical` ignores `mean`/`std`/`max_demand` and returns its fitted frequencies as-is.
+    fn pmf(&self, mean: f64, std: f64, max_demand: i32) -> Vec<f64> {
+        match self {
+            DemandModel::Normal => {
+                let raw = (0..=max_demand).map(|d| Self::normal_density(d as f64, mean, std)).collect();
+                Self::normalize(raw)
+            }
+            DemandModel::Poisson => {
+                let mut pmf = Vec::with_capacity((max_demand + 1) as usize);
+                let mut current = (-mean).exp();
+                pmf.push(current);
+                for k in 1..=max_demand {
+                    current *= mean / (k as f64);
+                    pmf.push(current);
+                }
+                Self::normalize(pmf)
+            }
+            DemandModel::NegativeBinomial => {
+                // Method-of-moments fit: variance = mean + mean^2 / r.
+                let variance = (std * std).max(mean + 1e-6);
+                let r = mean * mean / (variance - mean).max(1e-6);
+                let p = r / (r + mean);
+
+                let mut pmf = Vec::with_capacity((max_demand + 1) as usize);
+                let mut current = p.powf(r);
+                pmf.push(current);
+                for k in 1..=max_demand {
+                    current *= ((k as f64) - 1.0 + r) / (k as f64) * (1.0 - p);
+                    pmf.push(current);
+                }
+                Self::normalize(pmf)
+            }
+            DemandModel::Empirical { pmf } => pmf.clone(),
+        }
+    }
+
+    // Draws a single demand realization by inverting this model's pmf against a uniform draw.
+    fn sample(&self, mean: f64, std: f64, rng: &mut impl Rng) -> i32 {
+        let max_demand = match self {
+            DemandModel::Empirical { pmf } => (pmf.len() - 1) as i32,
+            _ => (mean + 4.0 * std).ceil() as i32,
+        };
+        let pmf = self.pmf(mean, std, max_demand);
+
+        let draw: f64 = rng.gen();
+        let mut cumulative = 0.0;
+        for (demand, &prob) in pmf.iter().enumerate() {
+            cumulative += prob;
+            if draw <= cumulative {
+                return demand as i32;
+            }
+        }
+        max_demand
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct TransportMode {
     name: String,
     cost: f64,
     time: usize,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Action {
+    quantity: usize,
+    mode_idx: usize,
+    price_idx: usize,
+}
+
+// Upper bound on transport lead time (the longest `TransportMode::time` below is 3), so the
+// in-transit pipeline can live in a fixed-size, stack-allocated array instead of a `Vec` that
+// would otherwise be heap-allocated on every `advance_pipeline`/`decode_state` call in the
+// value-iteration hot path.
+const MAX_LEAD_TIME: usize = 4;
+type Pipeline = [usize; MAX_LEAD_TIME];
+
+// The current in-place value_function update is Gauss-Seidel: each state's update can see
+// other states' updates from the same sweep. JacobiParallel instead computes every state's
+// update from the previous sweep's value_function alone, which makes updates independent and
+// safe to run concurrently via rayon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sweep {
+    GaussSeidel,
+    JacobiParallel,
+}
+
+// Variant names drop the shared "Iteration" suffix (clippy::enum_variant_names) since every
+// solver here is some kind of iteration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Solver {
+    Value { sweep: Sweep },
+    Policy,
+    ModifiedPolicy { eval_sweeps: usize },
+}
+
 #[derive(Debug)]
 struct MDPOptimizer {
     config: MDPConfig,
     value_function: Vec<f64>,
-    policy: Vec<usize>,
-    q_values: Vec<Vec<f64>>,
+    policy: Vec<Action>,
+    q_values: Vec<HashMap<Action, f64>>,
     transport_modes: Vec<TransportMode>,
+    price_adapter: Box<dyn PriceAdapter>,
+    demand_model: DemandModel,
+    max_lead_time: usize,
+    pipeline_combos: usize,
+    // Backorder mode lets on-hand go negative down to -max_backorder, so the on-hand dimension
+    // is encoded with this offset added: encoded 0 represents physical on-hand -on_hand_offset.
+    on_hand_offset: usize,
+    num_states: usize,
 }
 
 impl MDPOptimizer {
     fn new(config: MDPConfig) -> Self {
-        let size = config.max_inventory + 1;
+        Self::with_options(config, Box::new(ConstantElasticity { elasticity: 1.0 }), DemandModel::Normal)
+    }
+
+    fn with_price_adapter(config: MDPConfig, price_adapter: Box<dyn PriceAdapter>) -> Self {
+        Self::with_options(config, price_adapter, DemandModel::Normal)
+    }
+
+    fn with_options(config: MDPConfig, price_adapter: Box<dyn PriceAdapter>, demand_model: DemandModel) -> Self {
         let transport_modes = vec![
             TransportMode { name: "truck".to_string(), cost: 100.0, time: 1 },
             TransportMode { name: "ship".to_string(), cost: 50.0, time: 3 },
@@ -59,71 +266,217 @@ impl MDPOptimizer {
             TransportMode { name: "air".to_string(), cost: 200.0, time: 0 },
         ];
 
+        let max_lead_time = transport_modes.iter().map(|m| m.time).max().unwrap_or(0);
+        assert!(max_lead_time <= MAX_LEAD_TIME, "transport mode lead time exceeds MAX_LEAD_TIME");
+        let pipeline_combos = (config.max_pipeline_qty + 1).pow(max_lead_time as u32);
+        let on_hand_offset = match config.fulfillment_mode {
+            FulfillmentMode::Backorder => config.max_backorder,
+            FulfillmentMode::LostSales => 0,
+        };
+        let on_hand_levels = config.max_inventory + on_hand_offset + 1;
+        let num_states = on_hand_levels * pipeline_combos;
+
         MDPOptimizer {
             config,
-            value_function: vec![0.0; size],
-            policy: vec![0; size],
-            q_values: vec![vec![0.0; size]; size],
+            value_function: vec![0.0; num_states],
+            policy: vec![Action { quantity: 0, mode_idx: 0, price_idx: 0 }; num_states],
+            q_values: vec![HashMap::new(); num_states],
             transport_modes,
+            price_adapter,
+            demand_model,
+            max_lead_time,
+            pipeline_combos,
+            on_hand_offset,
+            num_states,
         }
     }
 
-    fn normal_pdf(&self, x: f64, mean: f64, std: f64) -> f64 {
-        let exponent = -0.5 * ((x - mean) / std).powi(2);
-        (1.0 / (std * (2.0 * PI).sqrt())) * exponent.exp()
+    fn encode_state(&self, on_hand: i32, pipeline: &Pipeline) -> usize {
+        let encoded_on_hand = (on_hand + self.on_hand_offset as i32) as usize;
+        let base = self.config.max_pipeline_qty + 1;
+        let mut pipeline_index = 0usize;
+        for slot in (0..self.max_lead_time).rev() {
+            pipeline_index = pipeline_index * base + pipeline[slot];
+        }
+        encoded_on_hand * self.pipeline_combos + pipeline_index
+    }
+
+    fn decode_state(&self, state_idx: usize) -> (i32, Pipeline) {
+        let encoded_on_hand = state_idx / self.pipeline_combos;
+        let mut remainder = state_idx % self.pipeline_combos;
+        let base = self.config.max_pipeline_qty + 1;
+        let mut pipeline: Pipeline = [0; MAX_LEAD_TIME];
+        for slot in pipeline.iter_mut().take(self.max_lead_time) {
+            *slot = remainder % base;
+            remainder /= base;
+        }
+        let on_hand = encoded_on_hand as i32 - self.on_hand_offset as i32;
+        (on_hand, pipeline)
     }
 
-    fn demand_probability(&self, d: i32) -> f64 {
-        if d < 0 {
-            return 0.0;
+    // Demand can only be served out of non-negative physical stock; a negative `on_hand`
+    // (an outstanding backlog) serves nothing.
+    fn apply_demand(&self, on_hand_after_receipt: i32, demand: i32) -> i32 {
+        let remaining = on_hand_after_receipt - demand;
+        match self.config.fulfillment_mode {
+            FulfillmentMode::Backorder => remaining.max(-(self.config.max_backorder as i32)),
+            FulfillmentMode::LostSales => remaining.max(0),
         }
-        self.normal_pdf(d as f64, self.config.demand_mean, self.config.demand_std)
     }
 
-    fn immediate_reward(&self, state: usize, action: usize, demand: i32) -> f64 {
-        let sales = (state as i32).min(demand) as f64;
-        let revenue = sales * self.config.selling_price;
-        let holding = (state as f64) * self.config.holding_cost;
-        let ordering = if action > 0 {
-            self.config.order_cost + (action as f64) * 5.0
+    // Per-period charge on an outstanding backlog, assessed on the destination state rather
+    // than a one-time stockout charge at the moment demand is missed.
+    fn backlog_penalty(&self, on_hand: i32) -> f64 {
+        match self.config.fulfillment_mode {
+            FulfillmentMode::Backorder => (-on_hand).max(0) as f64 * self.config.backorder_cost,
+            FulfillmentMode::LostSales => 0.0,
+        }
+    }
+
+    fn immediate_reward(&self, on_hand: i32, quantity: usize, mode: &TransportMode, price: f64, demand: i32) -> f64 {
+        let available = on_hand.max(0);
+        let sales = available.min(demand) as f64;
+        let revenue = sales * price;
+        let holding = (available as f64) * self.config.holding_cost;
+        let ordering = if quantity > 0 {
+            self.config.order_cost + (quantity as f64) * 5.0 + mode.cost
         } else {
             0.0
         };
-        let stockout = (0.max(demand - state as i32) as f64) * self.config.stockout_cost;
+        let stockout = match self.config.fulfillment_mode {
+            FulfillmentMode::LostSales => (0.max(demand - available) as f64) * self.config.stockout_cost,
+            FulfillmentMode::Backorder => 0.0,
+        };
         revenue - holding - ordering - stockout
     }
 
-    fn bellman_update(&mut self, state: usize) -> (f64, usize) {
-        let mut max_value = f64::NEG_INFINITY;
-        let mut best_action = 0;
-        let max_action = (self.config.max_inventory - state).min(self.config.max_inventory);
-
-        for action in 0..=max_action {
-            let mut expected_value = 0.0;
-            let max_demand = (self.config.demand_mean + 4.0 * self.config.demand_std) as i32;
-
-            for demand in 0..=max_demand {
-                let prob = self.demand_probability(demand);
-                let reward = self.immediate_reward(state, action, demand);
-                let next_state = 0.max(
-                    (self.config.max_inventory as i32)
-                        .min((state as i32) + (action as i32) - demand)
-                ) as usize;
-                expected_value += prob * (reward + self.config.gamma * self.value_function[next_state]);
+    // Advances on-hand inventory and the in-transit pipeline by one period: `on_hand` must
+    // already have this period's receipt (pipeline[0]) and demand applied. Returns a fixed-size
+    // array by value (stack-allocated, no `Vec`) since this runs once per (state, action,
+    // demand outcome) triple in the value-iteration hot path.
+    fn advance_pipeline(&self, on_hand_after_demand: i32, pipeline: &Pipeline, quantity: usize, mode: &TransportMode) -> (i32, Pipeline) {
+        let mut next_pipeline: Pipeline = [0; MAX_LEAD_TIME];
+        let shift = self.max_lead_time.saturating_sub(1);
+        next_pipeline[..shift].copy_from_slice(&pipeline[1..1 + shift]);
+        let mut next_on_hand = on_hand_after_demand;
+
+        if quantity > 0 {
+            if mode.time == 0 {
+                next_on_hand = (next_on_hand + quantity as i32).min(self.config.max_inventory as i32);
+            } else {
+                let slot = mode.time - 1;
+                next_pipeline[slot] = (next_pipeline[slot] + quantity).min(self.config.max_pipeline_qty);
             }
+        }
+
+        (next_on_hand, next_pipeline)
+    }
+
+    // Expected discounted value of taking a fixed `action` in `state_idx`, bootstrapping off
+    // the current `value_function`. Shared by the value-iteration argmax and by policy
+    // evaluation, which holds the action fixed instead of maximizing over it. The backorder
+    // penalty (when enabled) is charged on the destination state's backlog, so the period cost
+    // here depends on both the current state (via `on_hand_after_receipt`) and the next state.
+    fn evaluate_action(&self, state_idx: usize, action: Action) -> f64 {
+        let (on_hand, pipeline) = self.decode_state(state_idx);
+        let on_hand_after_receipt = (on_hand + pipeline[0] as i32).min(self.config.max_inventory as i32);
+        let mode = &self.transport_modes[action.mode_idx];
+        let price = self.config.price_levels[action.price_idx];
+        let on_hand_after_receipt_usize = on_hand_after_receipt.max(0) as usize;
+
+        let mean = self.price_adapter.demand_mean(price, on_hand_after_receipt_usize, &self.config);
+        let billed_price = self.price_adapter.effective_price(price, on_hand_after_receipt_usize, &self.config);
+        let std = mean * (self.config.demand_std / self.config.demand_mean);
+        let max_demand = (mean + 4.0 * std).ceil() as i32;
+        let pmf = self.demand_model.pmf(mean, std, max_demand);
 
-            self.q_values[state][action] = expected_value;
+        let mut expected_value = 0.0;
+        for (demand, &prob) in pmf.iter().enumerate() {
+            let demand = demand as i32;
+            let reward = self.immediate_reward(on_hand_after_receipt, action.quantity, mode, billed_price, demand);
 
-            if expected_value > max_value {
-                max_value = expected_value;
-                best_action = action;
+            let remaining = self.apply_demand(on_hand_after_receipt, demand);
+            let (next_on_hand, next_pipeline) = self.advance_pipeline(remaining, &pipeline, action.quantity, mode);
+            let next_state = self.encode_state(next_on_hand, &next_pipeline);
+            let period_cost = reward - self.backlog_penalty(next_on_hand);
+
+            expected_value += prob * (period_cost + self.config.gamma * self.value_function[next_state]);
+        }
+
+        expected_value
+    }
+
+    // How much of `max_pipeline_qty` is already spoken for in the slot an order placed via
+    // `mode` this period would land in, after this period's pipeline shift. A slot can hold
+    // in-transit stock shifted in from an earlier order placed via a *different* mode (e.g. an
+    // older ship order lands in the same slot a new rail order would), so the remaining
+    // headroom for a fresh order has to be checked per destination slot, not just against
+    // overall `max_inventory`.
+    fn pipeline_slot_occupancy(&self, pipeline: &Pipeline, mode: &TransportMode) -> usize {
+        if mode.time == 0 {
+            return 0;
+        }
+        let slot = mode.time - 1;
+        let shift = self.max_lead_time.saturating_sub(1);
+        if slot < shift { pipeline[slot + 1] } else { 0 }
+    }
+
+    // Read-only argmax over actions for `state_idx`, bootstrapping off the current
+    // `value_function`. Safe to call concurrently (e.g. from a rayon par_iter) since it never
+    // writes `q_values`; `bellman_update` wraps this for the single-threaded, q_values-tracking
+    // callers (Gauss-Seidel sweeps, policy improvement).
+    fn bellman_argmax(&self, state_idx: usize) -> (f64, Action) {
+        let (on_hand, pipeline) = self.decode_state(state_idx);
+        let on_hand_after_receipt = (on_hand + pipeline[0] as i32).min(self.config.max_inventory as i32);
+
+        let mut max_value = f64::NEG_INFINITY;
+        let mut best_action = Action { quantity: 0, mode_idx: 0, price_idx: 0 };
+        let max_order = ((self.config.max_inventory as i32 - on_hand_after_receipt).max(0) as usize)
+            .min(self.config.max_pipeline_qty);
+
+        for price_idx in 0..self.config.price_levels.len() {
+            for mode_idx in 0..self.transport_modes.len() {
+                let mode = &self.transport_modes[mode_idx];
+                let slot_headroom = self.config.max_pipeline_qty
+                    .saturating_sub(self.pipeline_slot_occupancy(&pipeline, mode));
+                let max_order_for_mode = max_order.min(slot_headroom);
+
+                // Quantity 0 behaves identically regardless of mode, so only evaluate it once.
+                let quantity_range = if mode_idx == 0 { 0..=max_order_for_mode } else { 1.min(max_order_for_mode + 1)..=max_order_for_mode };
+
+                for quantity in quantity_range {
+                    let action = Action { quantity, mode_idx, price_idx };
+                    let expected_value = self.evaluate_action(state_idx, action);
+
+                    if expected_value > max_value {
+                        max_value = expected_value;
+                        best_action = action;
+                    }
+                }
             }
         }
 
         (max_value, best_action)
     }
 
+    fn bellman_update(&mut self, state_idx: usize) -> (f64, Action) {
+        let (value, action) = self.bellman_argmax(state_idx);
+        self.q_values[state_idx].insert(action, value);
+        (value, action)
+    }
+
     fn value_iteration(&mut self, epsilon: f64, max_iterations: usize) -> ConvergenceInfo {
+        self.value_iteration_with_sweep(epsilon, max_iterations, Sweep::GaussSeidel)
+    }
+
+    fn value_iteration_with_sweep(&mut self, epsilon: f64, max_iterations: usize, sweep: Sweep) -> ConvergenceInfo {
+        match sweep {
+            Sweep::GaussSeidel => self.value_iteration_gauss_seidel(epsilon, max_iterations),
+            Sweep::JacobiParallel => self.value_iteration_jacobi_parallel(epsilon, max_iterations),
+        }
+    }
+
+    fn value_iteration_gauss_seidel(&mut self, epsilon: f64, max_iterations: usize) -> ConvergenceInfo {
         let mut convergence_info = ConvergenceInfo {
             converged: false,
             iterations: 0,
@@ -132,14 +485,14 @@ impl MDPOptimizer {
         };
 
         for iteration in 0..max_iterations {
-            let mut delta = 0.0;
+            let mut delta: f64 = 0.0;
 
-            for state in 0..=self.config.max_inventory {
-                let old_value = self.value_function[state];
-                let (new_value, best_action) = self.bellman_update(state);
+            for state_idx in 0..self.num_states {
+                let old_value = self.value_function[state_idx];
+                let (new_value, best_action) = self.bellman_update(state_idx);
                 delta = delta.max((old_value - new_value).abs());
-                self.value_function[state] = new_value;
-                self.policy[state] = best_action;
+                self.value_function[state_idx] = new_value;
+                self.policy[state_idx] = best_action;
             }
 
             convergence_info.delta_history.push(delta);
@@ -155,65 +508,199 @@ impl MDPOptimizer {
         convergence_info
     }
 
-    fn compute_s_s_policy(&self) -> (usize, usize) {
-        let mut reorder_points = Vec::new();
-        let mut order_up_to = Vec::new();
+    // Jacobi sweep: every state's (value, action) is computed from the previous iteration's
+    // value_function via rayon's par_iter, then the results are swapped in all at once. Unlike
+    // Gauss-Seidel, no state's update can see another state's update from the same iteration.
+    fn value_iteration_jacobi_parallel(&mut self, epsilon: f64, max_iterations: usize) -> ConvergenceInfo {
+        let mut convergence_info = ConvergenceInfo {
+            converged: false,
+            iterations: 0,
+            final_delta: 0.0,
+            delta_history: Vec::new(),
+        };
+
+        for iteration in 0..max_iterations {
+            let snapshot: &Self = &*self;
+            let updates: Vec<(f64, Action)> = (0..self.num_states)
+                .into_par_iter()
+                .map(|state_idx| snapshot.bellman_argmax(state_idx))
+                .collect();
+
+            let mut delta: f64 = 0.0;
+            let mut new_value_function = vec![0.0; self.num_states];
+            let mut new_policy = vec![Action { quantity: 0, mode_idx: 0, price_idx: 0 }; self.num_states];
 
-        for state in 0..=self.config.max_inventory {
-            if self.policy[state] > 0 {
-                reorder_points.push(state);
-                order_up_to.push(state + self.policy[state]);
+            for (state_idx, (new_value, best_action)) in updates.into_iter().enumerate() {
+                delta = delta.max((self.value_function[state_idx] - new_value).abs());
+                new_value_function[state_idx] = new_value;
+                new_policy[state_idx] = best_action;
+            }
+
+            self.value_function = new_value_function;
+            self.policy = new_policy;
+
+            convergence_info.delta_history.push(delta);
+            convergence_info.iterations = iteration + 1;
+            convergence_info.final_delta = delta;
+
+            if delta < epsilon {
+                convergence_info.converged = true;
+                break;
             }
         }
 
-        let s = reorder_points.iter().max().copied().unwrap_or(self.config.max_inventory / 3);
-        let S = if !order_up_to.is_empty() {
-            order_up_to.iter().sum::<usize>() / order_up_to.len()
-        } else {
-            (2 * self.config.max_inventory) / 3
+        convergence_info
+    }
+
+    fn solve(&mut self, solver: Solver, epsilon: f64, max_iterations: usize) -> ConvergenceInfo {
+        match solver {
+            Solver::Value { sweep } => self.value_iteration_with_sweep(epsilon, max_iterations, sweep),
+            Solver::Policy => self.policy_iteration(epsilon, max_iterations, None),
+            Solver::ModifiedPolicy { eval_sweeps } => {
+                self.policy_iteration(epsilon, max_iterations, Some(eval_sweeps))
+            }
+        }
+    }
+
+    // Alternates policy evaluation and policy improvement until the policy is stable.
+    // `eval_sweep_cap` bounds the number of evaluation sweeps per outer iteration: `None` runs
+    // evaluation to convergence (classic policy iteration), `Some(n)` truncates it at `n`
+    // sweeps (modified policy iteration), trading exactness for speed.
+    fn policy_iteration(&mut self, epsilon: f64, max_iterations: usize, eval_sweep_cap: Option<usize>) -> ConvergenceInfo {
+        let mut convergence_info = ConvergenceInfo {
+            converged: false,
+            iterations: 0,
+            final_delta: 0.0,
+            delta_history: Vec::new(),
         };
 
-        (s, S)
+        for iteration in 0..max_iterations {
+            let delta = self.policy_evaluation(epsilon, eval_sweep_cap);
+            convergence_info.delta_history.push(delta);
+            convergence_info.iterations = iteration + 1;
+            convergence_info.final_delta = delta;
+
+            let policy_stable = self.policy_improvement();
+            if policy_stable {
+                convergence_info.converged = true;
+                break;
+            }
+        }
+
+        convergence_info
+    }
+
+    // Holds `policy` fixed and sweeps V(s) = sum_d p(d)[r(s,pi(s),d) + gamma V(s')] until the
+    // max change drops below `epsilon` or `sweep_cap` sweeps have run. Returns the final delta.
+    // `None` (classic policy iteration, "evaluate to convergence") is still capped at a fixed
+    // 100 sweeps rather than left unbounded, since `policy_iteration`'s own outer loop can run
+    // up to `max_iterations` times and an uncapped inner loop would leave the worst case
+    // unbounded rather than `max_iterations * 100` sweeps.
+    fn policy_evaluation(&mut self, epsilon: f64, sweep_cap: Option<usize>) -> f64 {
+        let max_sweeps = sweep_cap.unwrap_or(100);
+        let mut delta: f64 = 0.0;
+
+        for _ in 0..max_sweeps {
+            delta = 0.0;
+            for state_idx in 0..self.num_states {
+                let action = self.policy[state_idx];
+                let new_value = self.evaluate_action(state_idx, action);
+                delta = delta.max((self.value_function[state_idx] - new_value).abs());
+                self.value_function[state_idx] = new_value;
+            }
+            if delta < epsilon {
+                break;
+            }
+        }
+
+        delta
+    }
+
+    // Runs the bellman argmax at every state and adopts the greedy action. Returns whether the
+    // policy was already stable (no action changed), the stopping condition for policy iteration.
+    fn policy_improvement(&mut self) -> bool {
+        let mut stable = true;
+
+        for state_idx in 0..self.num_states {
+            let (_, best_action) = self.bellman_update(state_idx);
+            if best_action != self.policy[state_idx] {
+                stable = false;
+            }
+            self.policy[state_idx] = best_action;
+        }
+
+        stable
     }
 
-    fn simulate_episode(&self, initial_state: usize, steps: usize, transport_mode: &str) -> SimulationResult {
+    // Reports the (s,S) reorder policy separately per transport mode, since the optimal
+    // order-up-to level now depends on which mode's lead time/cost tradeoff was chosen.
+    fn compute_s_s_policy(&self) -> HashMap<String, (i32, i32)> {
+        let mut reorder_points: HashMap<String, Vec<i32>> = HashMap::new();
+        let mut order_up_to: HashMap<String, Vec<i32>> = HashMap::new();
+
+        for state_idx in 0..self.num_states {
+            let action = self.policy[state_idx];
+            if action.quantity == 0 {
+                continue;
+            }
+            let (on_hand, _pipeline) = self.decode_state(state_idx);
+            let mode_name = self.transport_modes[action.mode_idx].name.clone();
+            reorder_points.entry(mode_name.clone()).or_default().push(on_hand);
+            order_up_to.entry(mode_name).or_default().push(on_hand + action.quantity as i32);
+        }
+
+        self.transport_modes.iter().map(|mode| {
+            let s = reorder_points.get(&mode.name)
+                .and_then(|points| points.iter().max().copied())
+                .unwrap_or((self.config.max_inventory / 3) as i32);
+            let order_up_to_levels = order_up_to.get(&mode.name);
+            let big_s = order_up_to_levels
+                .filter(|levels| !levels.is_empty())
+                .map(|levels| levels.iter().sum::<i32>() / levels.len() as i32)
+                .unwrap_or(((2 * self.config.max_inventory) / 3) as i32);
+            (mode.name.clone(), (s, big_s))
+        }).collect()
+    }
+
+    fn simulate_episode(&self, initial_on_hand: i32, steps: usize) -> SimulationResult {
         let mut rng = rand::thread_rng();
-        let normal = Normal::new(self.config.demand_mean, self.config.demand_std);
         let mut trajectory = Vec::new();
-        let mut state = initial_state;
+        let mut on_hand = initial_on_hand;
+        let mut pipeline: Pipeline = [0; MAX_LEAD_TIME];
         let mut total_reward = 0.0;
 
-        let transport_cost = self.transport_modes
-            .iter()
-            .find(|m| m.name == transport_mode)
-            .map(|m| m.cost)
-            .unwrap_or(0.0);
-
         for step in 0..steps {
-            let action = self.policy[state];
-            let demand = normal.sample(&mut rng).round().max(0.0) as i32;
-            let mut reward = self.immediate_reward(state, action, demand);
+            let state_idx = self.encode_state(on_hand, &pipeline);
+            let action = self.policy[state_idx];
+            let mode = &self.transport_modes[action.mode_idx];
+            let price = self.config.price_levels[action.price_idx];
 
-            if action > 0 {
-                reward -= transport_cost;
-            }
+            let on_hand_after_receipt = (on_hand + pipeline[0] as i32).min(self.config.max_inventory as i32);
+            let on_hand_after_receipt_usize = on_hand_after_receipt.max(0) as usize;
+            let mean = self.price_adapter.demand_mean(price, on_hand_after_receipt_usize, &self.config);
+            let billed_price = self.price_adapter.effective_price(price, on_hand_after_receipt_usize, &self.config);
+            let std = mean * (self.config.demand_std / self.config.demand_mean);
+            let demand = self.demand_model.sample(mean, std, &mut rng);
+            let reward = self.immediate_reward(on_hand_after_receipt, action.quantity, mode, billed_price, demand);
 
-            let next_state = 0.max(
-                (self.config.max_inventory as i32)
-                    .min((state as i32) + (action as i32) - demand)
-            ) as usize;
+            let remaining = self.apply_demand(on_hand_after_receipt, demand);
+            let (next_on_hand, next_pipeline) = self.advance_pipeline(remaining, &pipeline, action.quantity, mode);
+            let period_cost = reward - self.backlog_penalty(next_on_hand);
 
             trajectory.push(SimulationStep {
                 step,
-                state,
-                action,
+                on_hand,
+                quantity: action.quantity,
+                mode: mode.name.clone(),
+                price,
                 demand: demand as usize,
-                reward,
-                next_state,
+                reward: period_cost,
+                next_on_hand,
             });
 
-            total_reward += reward;
-            state = next_state;
+            total_reward += period_cost;
+            on_hand = next_on_hand;
+            pipeline = next_pipeline;
         }
 
         SimulationResult {
@@ -223,34 +710,53 @@ impl MDPOptimizer {
         }
     }
 
+    // The optimal selling price at each state, for states with an empty pipeline.
+    fn price_policy(&self) -> Vec<f64> {
+        let empty_pipeline: Pipeline = [0; MAX_LEAD_TIME];
+        (-(self.on_hand_offset as i32)..=(self.config.max_inventory as i32))
+            .map(|on_hand| {
+                let state_idx = self.encode_state(on_hand, &empty_pipeline);
+                self.config.price_levels[self.policy[state_idx].price_idx]
+            })
+            .collect()
+    }
+
     fn export_results(&self, filename: &str) -> std::io::Result<()> {
-        let (s, S) = self.compute_s_s_policy();
-        
+        let s_s_policy_by_mode = self.compute_s_s_policy();
+        let price_policy = self.price_policy();
+
         let results = OptimizationResults {
             config: self.config.clone(),
             value_function: self.value_function.clone(),
             policy: self.policy.clone(),
-            s_policy: s,
-            S_policy: S,
+            s_s_policy_by_mode,
+            price_policy,
             transport_modes: self.transport_modes.clone(),
         };
 
         let json = serde_json::to_string_pretty(&results)?;
         let mut file = File::create(filename)?;
         file.write_all(json.as_bytes())?;
-        
+
         Ok(())
     }
 
     fn print_policy(&self, max_states: usize) {
-        println!("\nOptimal Policy (first {} states):", max_states);
-        println!("{:>8} {:>12} {:>15}", "State", "Action", "Value");
-        println!("{}", "-".repeat(35));
+        println!("\nOptimal Policy (first {} on-hand levels, empty pipeline):", max_states);
+        println!("{:>8} {:>10} {:>10} {:>10} {:>15}", "OnHand", "Qty", "Mode", "Price", "Value");
+        println!("{}", "-".repeat(56));
 
-        for state in 0..max_states.min(self.config.max_inventory + 1) {
+        let empty_pipeline: Pipeline = [0; MAX_LEAD_TIME];
+        let lower = -(self.on_hand_offset as i32);
+        let upper = lower + max_states.min(self.config.max_inventory + self.on_hand_offset + 1) as i32 - 1;
+        for on_hand in lower..=upper {
+            let state_idx = self.encode_state(on_hand, &empty_pipeline);
+            let action = self.policy[state_idx];
+            let mode_name = &self.transport_modes[action.mode_idx].name;
+            let price = self.config.price_levels[action.price_idx];
             println!(
-                "{:>8} {:>12} {:>15.2}",
-                state, self.policy[state], self.value_function[state]
+                "{:>8} {:>10} {:>10} {:>10.2} {:>15.2}",
+                on_hand, action.quantity, mode_name, price, self.value_function[state_idx]
             );
         }
     }
@@ -267,11 +773,13 @@ struct ConvergenceInfo {
 #[derive(Debug, Serialize)]
 struct SimulationStep {
     step: usize,
-    state: usize,
-    action: usize,
+    on_hand: i32,
+    quantity: usize,
+    mode: String,
+    price: f64,
     demand: usize,
     reward: f64,
-    next_state: usize,
+    next_on_hand: i32,
 }
 
 #[derive(Debug, Serialize)]
@@ -285,17 +793,27 @@ struct SimulationResult {
 struct OptimizationResults {
     config: MDPConfig,
     value_function: Vec<f64>,
-    policy: Vec<usize>,
-    s_policy: usize,
-    S_policy: usize,
+    policy: Vec<Action>,
+    s_s_policy_by_mode: HashMap<String, (i32, i32)>,
+    price_policy: Vec<f64>,
     transport_modes: Vec<TransportMode>,
 }
 
 fn main() {
     println!("=== MDP Inventory Optimizer (Rust) ===\n");
 
-    let config = MDPConfig::default();
-    let mut optimizer = MDPOptimizer::new(config);
+    // This walkthrough runs seven full solves back to back (value iteration with both sweeps,
+    // policy iteration, modified policy iteration, a LinearMarkdown solve, and a
+    // lost-sales/backorder comparison). MDPConfig::default()'s full-size state
+    // space (101 on-hand levels x up to 216 pipeline combos, 5 prices x 4 modes of actions) is
+    // meant for real runs, not for a demo printed straight to a terminal, so scale it down here.
+    let config = MDPConfig {
+        max_inventory: 20,
+        max_pipeline_qty: 2,
+        price_levels: vec![12.5, 15.0, 17.5],
+        ..MDPConfig::default()
+    };
+    let mut optimizer = MDPOptimizer::new(config.clone());
 
     println!("Running Value Iteration...");
     let convergence_info = optimizer.value_iteration(0.01, 1000);
@@ -305,15 +823,87 @@ fn main() {
     println!("  Iterations: {}", convergence_info.iterations);
     println!("  Final Delta: {:.6}", convergence_info.final_delta);
 
-    let (s, S) = optimizer.compute_s_s_policy();
-    println!("\nOptimal (s,S) Policy:");
-    println!("  s (reorder point): {}", s);
-    println!("  S (order-up-to level): {}", S);
+    println!("\nRunning Value Iteration with the parallel Jacobi sweep for comparison...");
+    let mut jacobi_optimizer = MDPOptimizer::new(config.clone());
+    let jacobi_convergence_info = jacobi_optimizer.solve(Solver::Value { sweep: Sweep::JacobiParallel }, 0.01, 1000);
+    println!("  Converged: {}", jacobi_convergence_info.converged);
+    println!("  Iterations: {}", jacobi_convergence_info.iterations);
+
+    println!("\nRunning Policy Iteration for comparison...");
+    let mut pi_optimizer = MDPOptimizer::new(config.clone());
+    let pi_convergence_info = pi_optimizer.solve(Solver::Policy, 0.01, 1000);
+    println!("  Converged: {}", pi_convergence_info.converged);
+    println!("  Outer Iterations: {}", pi_convergence_info.iterations);
+
+    println!("\nRunning Modified Policy Iteration (eval_sweeps = 5) for comparison...");
+    let mut mpi_optimizer = MDPOptimizer::new(config.clone());
+    let mpi_convergence_info = mpi_optimizer.solve(Solver::ModifiedPolicy { eval_sweeps: 5 }, 0.01, 1000);
+    println!("  Converged: {}", mpi_convergence_info.converged);
+    println!("  Outer Iterations: {}", mpi_convergence_info.iterations);
+
+    let s_s_policy_by_mode = optimizer.compute_s_s_policy();
+    println!("\nOptimal (s,S) Policy by Transport Mode:");
+    for (mode, (s, big_s)) in &s_s_policy_by_mode {
+        println!("  {}: s (reorder point) = {}, S (order-up-to level) = {}", mode, s, big_s);
+    }
 
     optimizer.print_policy(20);
 
+    println!("\nComparing ConstantElasticity vs LinearMarkdown price adapters...");
+    let mut markdown_optimizer = MDPOptimizer::with_price_adapter(
+        config.clone(),
+        Box::new(LinearMarkdown { elasticity: 1.2, markdown_rate: 0.3 }),
+    );
+    markdown_optimizer.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.01, 1000);
+    println!("  ConstantElasticity price policy: {:?}", optimizer.price_policy());
+    println!("  LinearMarkdown price policy:     {:?}", markdown_optimizer.price_policy());
+
+    println!("\nComparing demand model shapes for mean={}, std={}...", config.demand_mean, config.demand_std);
+    let max_demand = (config.demand_mean + 4.0 * config.demand_std).ceil() as i32;
+    for (name, model) in [
+        ("Normal", DemandModel::Normal),
+        ("Poisson", DemandModel::Poisson),
+        ("NegativeBinomial", DemandModel::NegativeBinomial),
+    ] {
+        let pmf = model.pmf(config.demand_mean, config.demand_std, max_demand);
+        println!(
+            "  {}: P(demand=0) = {:.4}, P(demand={}) = {:.4}",
+            name, pmf[0], config.demand_mean as i32, pmf[config.demand_mean as usize]
+        );
+    }
+    let historical_samples = vec![8, 9, 10, 10, 11, 12, 9, 10, 8, 13];
+    let empirical = DemandModel::fit_empirical(&historical_samples);
+    println!(
+        "  Empirical (fit from {} historical samples): P(demand=10) = {:.4}",
+        historical_samples.len(),
+        empirical.pmf(0.0, 0.0, 0)[10]
+    );
+
+    println!("\nComparing Lost-Sales vs Backorder fulfillment modes...");
+    let lost_sales_config = MDPConfig {
+        fulfillment_mode: FulfillmentMode::LostSales,
+        ..config.clone()
+    };
+    let backorder_config = MDPConfig {
+        fulfillment_mode: FulfillmentMode::Backorder,
+        ..config
+    };
+    let mut lost_sales_optimizer = MDPOptimizer::new(lost_sales_config);
+    lost_sales_optimizer.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.01, 1000);
+    let mut backorder_optimizer = MDPOptimizer::new(backorder_config);
+    backorder_optimizer.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.01, 1000);
+
+    println!("\n(s,S) Policy under Lost-Sales:");
+    for (mode, (s, big_s)) in &lost_sales_optimizer.compute_s_s_policy() {
+        println!("  {}: s = {}, S = {}", mode, s, big_s);
+    }
+    println!("(s,S) Policy under Backorder:");
+    for (mode, (s, big_s)) in &backorder_optimizer.compute_s_s_policy() {
+        println!("  {}: s = {}, S = {}", mode, s, big_s);
+    }
+
     println!("\nRunning simulation (30 steps)...");
-    let sim_result = optimizer.simulate_episode(50, 30, "truck");
+    let sim_result = optimizer.simulate_episode(10, 30);
     println!("  Total Reward: ${:.2}", sim_result.total_reward);
     println!("  Average Reward: ${:.2}", sim_result.average_reward);
 
@@ -323,4 +913,266 @@ fn main() {
     }
 
     println!("\n=== Optimization Complete ===");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // encode_state/decode_state flatten (on_hand, pipeline) into a single usize and back;
+    // a mismatch here would silently corrupt every lookup into value_function/policy.
+    #[test]
+    fn encode_decode_state_round_trips_across_the_on_hand_range() {
+        let config = MDPConfig { max_inventory: 8, max_pipeline_qty: 2, ..MDPConfig::default() };
+        let optimizer = MDPOptimizer::new(config.clone());
+
+        for on_hand in -(optimizer.on_hand_offset as i32)..=(config.max_inventory as i32) {
+            // Only the first `max_lead_time` slots round-trip; slot 3 is unused since the
+            // default transport modes' longest lead time (ship, 3 periods) only needs slots 0-2.
+            let pipeline: Pipeline = [1, 2, 0, 0];
+            let state_idx = optimizer.encode_state(on_hand, &pipeline);
+            let (decoded_on_hand, decoded_pipeline) = optimizer.decode_state(state_idx);
+            assert_eq!(decoded_on_hand, on_hand);
+            assert_eq!(decoded_pipeline, pipeline);
+        }
+    }
+
+    // Backorder mode lets on-hand go negative down to -max_backorder, encoded with
+    // `on_hand_offset` added so it still fits a non-negative usize index. The boundary (the
+    // most negative on-hand the model ever represents) is the riskiest point for an off-by-one
+    // in that offset arithmetic, so check it round-trips explicitly rather than only the
+    // ordinary non-negative range the LostSales tests above exercise.
+    #[test]
+    fn encode_decode_state_round_trips_at_the_backorder_boundary() {
+        let config = MDPConfig {
+            max_inventory: 8,
+            max_pipeline_qty: 2,
+            max_backorder: 5,
+            fulfillment_mode: FulfillmentMode::Backorder,
+            ..MDPConfig::default()
+        };
+        let optimizer = MDPOptimizer::new(config.clone());
+        let pipeline: Pipeline = [1, 2, 0, 0];
+
+        for on_hand in -(config.max_backorder as i32)..=(config.max_inventory as i32) {
+            let state_idx = optimizer.encode_state(on_hand, &pipeline);
+            let (decoded_on_hand, decoded_pipeline) = optimizer.decode_state(state_idx);
+            assert_eq!(decoded_on_hand, on_hand);
+            assert_eq!(decoded_pipeline, pipeline);
+        }
+
+        // The boundary itself: encoded index 0 is the most backlogged representable state.
+        let boundary_idx = optimizer.encode_state(-(config.max_backorder as i32), &[0; MAX_LEAD_TIME]);
+        assert_eq!(boundary_idx, 0);
+    }
+
+    // LostSales never charges a per-period backlog penalty (it charges a one-time stockout
+    // cost instead, inside `immediate_reward`); Backorder does, proportional to the backlog.
+    // This is the mechanism that should make the two modes' value functions genuinely diverge,
+    // not just a labeling difference.
+    #[test]
+    fn backlog_penalty_is_mode_dependent() {
+        let backorder_config = MDPConfig {
+            fulfillment_mode: FulfillmentMode::Backorder,
+            backorder_cost: 2.0,
+            ..MDPConfig::default()
+        };
+        let backorder_optimizer = MDPOptimizer::new(backorder_config);
+        assert_eq!(backorder_optimizer.backlog_penalty(-3), 6.0);
+        assert_eq!(backorder_optimizer.backlog_penalty(5), 0.0);
+
+        let lost_sales_optimizer = MDPOptimizer::new(MDPConfig::default());
+        assert_eq!(lost_sales_optimizer.backlog_penalty(-3), 0.0);
+    }
+
+    // Backorder and LostSales are economically different policies (one pays a per-period
+    // backlog cost, the other a one-time stockout cost), and that should show up as a real
+    // difference in the solved value function at the same physical on-hand level, even though
+    // compute_s_s_policy's coarse (s,S) summary can end up printing the same numbers for both.
+    #[test]
+    fn backorder_and_lost_sales_solve_to_different_value_functions() {
+        let base = MDPConfig {
+            max_inventory: 8,
+            max_pipeline_qty: 1,
+            max_backorder: 5,
+            stockout_cost: 50.0,
+            backorder_cost: 1.0,
+            ..MDPConfig::default()
+        };
+        let lost_sales_config = MDPConfig { fulfillment_mode: FulfillmentMode::LostSales, ..base.clone() };
+        let backorder_config = MDPConfig { fulfillment_mode: FulfillmentMode::Backorder, ..base };
+
+        let mut lost_sales_optimizer = MDPOptimizer::new(lost_sales_config);
+        lost_sales_optimizer.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.1, 200);
+        let mut backorder_optimizer = MDPOptimizer::new(backorder_config);
+        backorder_optimizer.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.1, 200);
+
+        let empty_pipeline: Pipeline = [0; MAX_LEAD_TIME];
+        let lost_sales_idx = lost_sales_optimizer.encode_state(0, &empty_pipeline);
+        let backorder_idx = backorder_optimizer.encode_state(0, &empty_pipeline);
+
+        assert!(
+            (lost_sales_optimizer.value_function[lost_sales_idx] - backorder_optimizer.value_function[backorder_idx]).abs() > 1.0,
+            "allowing backlog instead of losing sales should noticeably change the expected value at on_hand = 0"
+        );
+    }
+
+    // Two different transport modes can have in-transit stock landing in the same pipeline
+    // slot in the same period (e.g. an older ship order's remaining lead time matches a new
+    // rail order's lead time). Ordering more via the new mode than the slot has headroom for
+    // must not silently drop units while still charging the full ordering cost.
+    #[test]
+    fn advance_pipeline_caps_quantity_by_destination_slot_headroom_not_just_inventory() {
+        let config = MDPConfig { max_inventory: 100, max_pipeline_qty: 5, ..MDPConfig::default() };
+        let optimizer = MDPOptimizer::new(config.clone());
+        let rail = TransportMode { name: "rail".to_string(), cost: 75.0, time: 2 };
+
+        // Slot 1 (rail's landing slot, time - 1 = 1) already holds pipeline[2] = 5, the cap.
+        let pipeline: Pipeline = [0, 0, 5, 0];
+        let (_, next_pipeline) = optimizer.advance_pipeline(10, &pipeline, 4, &rail);
+
+        assert_eq!(next_pipeline[1], 5, "slot should stay at the cap, not overflow past it");
+
+        let occupancy = optimizer.pipeline_slot_occupancy(&pipeline, &rail);
+        assert_eq!(occupancy, config.max_pipeline_qty, "slot is already at capacity: bellman_argmax should see zero headroom left");
+    }
+
+    // LinearMarkdown was added for the markdown/clearance problem (price drops as on-hand
+    // stock rises) but was never reachable outside of `with_price_adapter`; this exercises
+    // it end-to-end so it's no longer dead code and the adapter actually produces a policy.
+    #[test]
+    fn linear_markdown_adapter_is_reachable_via_with_price_adapter() {
+        let config = MDPConfig {
+            max_inventory: 10,
+            max_pipeline_qty: 1,
+            price_levels: vec![10.0, 15.0, 20.0],
+            ..MDPConfig::default()
+        };
+        let mut optimizer = MDPOptimizer::with_price_adapter(
+            config,
+            Box::new(LinearMarkdown { elasticity: 1.2, markdown_rate: 0.3 }),
+        );
+
+        optimizer.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.1, 50);
+
+        assert_eq!(optimizer.policy.len(), optimizer.num_states);
+    }
+
+    // LinearMarkdown must bill revenue at the same marked-down price it uses to stimulate
+    // demand; otherwise the model gets the demand benefit of a price cut for free and never
+    // has a reason to pick it over the nominal price.
+    #[test]
+    fn linear_markdown_bills_revenue_at_the_same_effective_price_it_uses_for_demand() {
+        let config = MDPConfig::default();
+        let adapter = LinearMarkdown { elasticity: 1.2, markdown_rate: 0.3 };
+        let price = 20.0;
+        let on_hand = 50;
+
+        let effective_price = adapter.effective_price(price, on_hand, &config);
+        assert!(effective_price < price, "markdown should reduce the billed price below nominal");
+
+        let stimulated_mean = adapter.demand_mean(price, on_hand, &config);
+        let nominal_mean = ConstantElasticity { elasticity: adapter.elasticity }.demand_mean(price, on_hand, &config);
+        assert!(
+            stimulated_mean > nominal_mean,
+            "the markdown-adjusted demand mean should exceed what the nominal price alone would imply"
+        );
+
+        let mean_at_billed_price = ConstantElasticity { elasticity: adapter.elasticity }.demand_mean(effective_price, on_hand, &config);
+        assert!(
+            (stimulated_mean - mean_at_billed_price).abs() < 1e-9,
+            "demand should be driven by exactly the price that is billed"
+        );
+    }
+
+    // DemandModel::Poisson/NegativeBinomial/Empirical (and fit_empirical) were only reachable
+    // by hand-constructing the enum, with no caller anywhere in the crate; exercise each one's
+    // pmf/sample directly so they're no longer dead code and their pmfs are actually valid
+    // (non-negative, normalized).
+    fn assert_is_valid_pmf(pmf: &[f64]) {
+        assert!(!pmf.is_empty());
+        assert!(pmf.iter().all(|&p| p >= 0.0));
+        let total: f64 = pmf.iter().sum();
+        assert!((total - 1.0).abs() < 1e-6, "pmf should sum to 1, got {}", total);
+    }
+
+    #[test]
+    fn poisson_pmf_is_normalized() {
+        let pmf = DemandModel::Poisson.pmf(10.0, 3.0, 30);
+        assert_is_valid_pmf(&pmf);
+    }
+
+    #[test]
+    fn negative_binomial_pmf_is_normalized() {
+        let pmf = DemandModel::NegativeBinomial.pmf(10.0, 5.0, 40);
+        assert_is_valid_pmf(&pmf);
+    }
+
+    #[test]
+    fn empirical_pmf_matches_fitted_frequencies() {
+        let samples = vec![2, 2, 3, 3, 3, 5];
+        let model = DemandModel::fit_empirical(&samples);
+        let pmf = model.pmf(0.0, 0.0, 0);
+        assert_is_valid_pmf(&pmf);
+        assert!((pmf[3] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sample_stays_within_each_models_support() {
+        let mut rng = rand::thread_rng();
+        for model in [DemandModel::Normal, DemandModel::Poisson, DemandModel::NegativeBinomial] {
+            for _ in 0..20 {
+                let demand = model.sample(10.0, 3.0, &mut rng);
+                assert!(demand >= 0);
+                // Support is 0..=ceil(mean + 4 * std) = 0..=22.
+                assert!(demand <= 22);
+            }
+        }
+    }
+
+    // JacobiParallel was never constructed anywhere (cargo clippy flagged it as dead code) and
+    // this request's stated benefit - a parallel speedup at a large state count - was never
+    // demonstrated. This checks the cheap case (policies agree on a tractable config) on every
+    // `cargo test`, and the expensive case (speedup at max_inventory = 5000, as the request
+    // specifically asked for) only when run explicitly via `cargo test -- --ignored`.
+    #[test]
+    fn jacobi_parallel_matches_gauss_seidel_on_small_config() {
+        let config = MDPConfig {
+            max_inventory: 8,
+            max_pipeline_qty: 1,
+            price_levels: vec![15.0],
+            ..MDPConfig::default()
+        };
+
+        let mut gauss_seidel = MDPOptimizer::new(config.clone());
+        gauss_seidel.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.01, 500);
+
+        let mut jacobi = MDPOptimizer::new(config);
+        jacobi.solve(Solver::Value { sweep: Sweep::JacobiParallel }, 0.01, 500);
+
+        assert_eq!(gauss_seidel.policy, jacobi.policy);
+    }
+
+    #[test]
+    #[ignore = "slow: run explicitly with `cargo test -- --ignored` to benchmark at scale"]
+    fn jacobi_parallel_is_faster_than_gauss_seidel_at_max_inventory_5000() {
+        let config = MDPConfig { max_inventory: 5000, ..MDPConfig::default() };
+
+        let mut gauss_seidel = MDPOptimizer::new(config.clone());
+        let gauss_seidel_start = std::time::Instant::now();
+        gauss_seidel.solve(Solver::Value { sweep: Sweep::GaussSeidel }, 0.01, 5);
+        let gauss_seidel_elapsed = gauss_seidel_start.elapsed();
+
+        let mut jacobi = MDPOptimizer::new(config);
+        let jacobi_start = std::time::Instant::now();
+        jacobi.solve(Solver::Value { sweep: Sweep::JacobiParallel }, 0.01, 5);
+        let jacobi_elapsed = jacobi_start.elapsed();
+
+        assert_eq!(gauss_seidel.policy, jacobi.policy);
+        assert!(
+            jacobi_elapsed < gauss_seidel_elapsed,
+            "expected JacobiParallel ({:?}) to be faster than GaussSeidel ({:?}) at this scale",
+            jacobi_elapsed, gauss_seidel_elapsed,
+        );
+    }
+}